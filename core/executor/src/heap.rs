@@ -40,6 +40,18 @@ enum Node {
 	Split,
 }
 
+/// Reason why `Heap::allocate` or `Heap::reallocate` could not satisfy a request.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Error {
+	/// The request is larger than the heap could ever serve, even if it were completely empty.
+	RequestTooLarge,
+	/// The heap has no free block large enough to serve the request right now.
+	OutOfMemory,
+	/// `reallocate` was called with a `ptr` that isn't currently allocated (already freed, or
+	/// never returned by `allocate`/`reallocate` in the first place).
+	PointerInvalid,
+}
+
 /// A buddy allocation heap, which tracks allocations and deallocations
 /// using a binary tree.
 pub struct Heap {
@@ -47,6 +59,31 @@ pub struct Heap {
 	levels: u32,
 	tree: vec::Vec<Node>,
 	total_size: u32,
+	// `level_free_lists[level]` holds the tree index of every currently `Free` node at that
+	// level, so a matching block can be grabbed in O(1) instead of descending the tree.
+	level_free_lists: vec::Vec<vec::Vec<usize>>,
+	// Bit `level` is set iff `level_free_lists[level]` is non-empty. Lets `allocate_block_in_tree`
+	// jump straight to the smallest sufficiently-large level that has a free node via
+	// `trailing_zeros`, rather than descending and backtracking through the tree.
+	orders_map: u32,
+	// Peak `total_size` ever reached over the heap's lifetime.
+	max_size: u32,
+}
+
+/// A point-in-time snapshot of a `Heap`'s usage, returned by `Heap::stats`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stats {
+	/// Bytes currently allocated.
+	pub total_size: u32,
+	/// Highest `total_size` has ever reached over the heap's lifetime.
+	pub max_size: u32,
+	/// Number of allocations currently live.
+	pub allocations: usize,
+	/// Largest single allocation (in Bytes) the heap could satisfy right now.
+	pub largest_free_block: u32,
+	/// Fraction in `[0, 1]` of the heap's free Bytes that lie outside one `largest_free_block`
+	/// sized block and so cannot be handed out as part of one single allocation.
+	pub fragmentation: f32,
 }
 
 impl Heap {
@@ -57,125 +94,397 @@ impl Heap {
 		let levels = Heap::get_tree_levels(leaves);
 		let node_count: usize = (1 << levels + 1) - 1;
 
+		let mut level_free_lists = vec![vec::Vec::new(); levels as usize + 1];
+		level_free_lists[levels as usize].push(0);
+
 		Heap {
 			allocated_bytes: FnvHashMap::default(),
 			levels,
 			tree: vec![Node::Free; node_count],
 			total_size: 0,
+			level_free_lists,
+			orders_map: 1 << levels,
+			max_size: 0,
+		}
+	}
+
+	/// Returns a snapshot of the heap's current usage and lifetime peak, for diagnosing runtime
+	/// out-of-memory conditions and sizing reserved memory.
+	pub fn stats(&self) -> Stats {
+		let largest_free_block = self.largest_free_block();
+		let total_free = self.total_free_bytes();
+		let fragmentation = if total_free == 0 {
+			0.0
+		} else {
+			(total_free - largest_free_block) as f32 / total_free as f32
+		};
+
+		Stats {
+			total_size: self.total_size,
+			max_size: self.max_size,
+			allocations: self.allocated_bytes.len(),
+			largest_free_block,
+			fragmentation,
+		}
+	}
+
+	fn bump_max_size(&mut self) {
+		if self.total_size > self.max_size {
+			self.max_size = self.total_size;
+		}
+	}
+
+	// Largest block the heap could currently serve in a single allocation, found from the
+	// highest level `orders_map` reports as non-empty.
+	fn largest_free_block(&self) -> u32 {
+		if self.orders_map == 0 {
+			0
+		} else {
+			let level = 31 - self.orders_map.leading_zeros();
+			(1u32 << level) * BLOCK_SIZE
+		}
+	}
+
+	// Total free Bytes across the whole tree, found by scanning every `Split` node's subtree
+	// for the `Free` nodes beneath it (a fully free subtree would already have been coalesced
+	// by `update_parent_nodes`, so this only ever finds blocks smaller than their parent).
+	fn total_free_bytes(&self) -> u32 {
+		self.free_bytes_in_subtree(0, self.levels)
+	}
+
+	fn free_bytes_in_subtree(&self, index: usize, level: u32) -> u32 {
+		match self.tree[index] {
+			Node::Free => (1u32 << level) * BLOCK_SIZE,
+			Node::Full => 0,
+			Node::Split => {
+				self.free_bytes_in_subtree(index * 2 + 1, level - 1)
+					+ self.free_bytes_in_subtree(index * 2 + 2, level - 1)
+			},
+		}
+	}
+
+	// Registers a `Free` node at `level` in the free-list index.
+	fn push_free_node(&mut self, level: u32, index: usize) {
+		self.level_free_lists[level as usize].push(index);
+		self.orders_map |= 1 << level;
+	}
+
+	// Removes a specific node from its level's free-list index, e.g. because it is about to be
+	// split or merged away. No-op if the node isn't present (it may never have been registered).
+	fn remove_free_node(&mut self, level: u32, index: usize) {
+		let list = &mut self.level_free_lists[level as usize];
+		if let Some(pos) = list.iter().position(|&candidate| candidate == index) {
+			list.swap_remove(pos);
+		}
+		if list.is_empty() {
+			self.orders_map &= !(1 << level);
+		}
+	}
+
+	// Pops an arbitrary free node at `level`. Panics if the level's free-list is empty; callers
+	// must only invoke this for a level `orders_map` reports as non-empty.
+	fn pop_free_node(&mut self, level: u32) -> usize {
+		let list = &mut self.level_free_lists[level as usize];
+		let index = list.pop().expect("orders_map bit was set, so the free-list is non-empty");
+		if list.is_empty() {
+			self.orders_map &= !(1 << level);
 		}
+		index
 	}
 
 	/// Gets requested number of bytes to allocate and returns an index offset.
 	/// The index offset starts at 0.
-	pub fn allocate(&mut self, size: u32) -> u32 {
+	pub fn allocate(&mut self, size: u32) -> Result<u32, Error> {
 		// Get the requested level from number of blocks requested
 		let blocks_needed = (size + BLOCK_SIZE - 1) / BLOCK_SIZE;
-		let block_offset = match self.allocate_block_in_tree(blocks_needed) {
-			Some(v) => v,
-			None => return 0,
-		};
+		let block_offset = self.allocate_block_in_tree(blocks_needed)?;
 
 		let ptr = BLOCK_SIZE * block_offset as u32;
 		self.allocated_bytes.insert(ptr, size as u32);
 
 		self.total_size += size;
+		self.bump_max_size();
 		trace!(target: "wasm-heap", "Heap size over {} Bytes after allocation", self.total_size);
 
-		ptr + 1
+		Ok(ptr + 1)
 	}
 
-	fn allocate_block_in_tree(&mut self, blocks_needed: u32) -> Option<usize> {
+	fn allocate_block_in_tree(&mut self, blocks_needed: u32) -> Result<usize, Error> {
 		let levels_needed = Heap::get_tree_levels(blocks_needed);
 		if levels_needed > self.levels {
 			trace!(target: "wasm-heap", "Heap is too small: {:?} > {:?}", levels_needed, self.levels);
-			return None;
+			return Err(Error::RequestTooLarge);
 		}
 
-		// Start at tree root and traverse down
-		let mut index = 0;
-		let mut current_level = self.levels;
-		'down: loop {
-			let buddy_exists = index & 1 == 1;
+		// Jump directly to the smallest level at or above `levels_needed` that has a free node,
+		// via the `orders_map` bitmap, instead of descending the tree and backtracking on
+		// dead ends.
+		let candidate_levels = self.orders_map >> levels_needed;
+		if candidate_levels == 0 {
+			trace!(target: "wasm-heap", "Heap is full: no free block at or above level {:?}", levels_needed);
+			return Err(Error::OutOfMemory);
+		}
+		let mut current_level = levels_needed + candidate_levels.trailing_zeros();
+		let mut index = self.pop_free_node(current_level);
+
+		// Split the found node down to the requested level, pushing each now-unused buddy half
+		// onto its own level's free-list as we go.
+		while current_level > levels_needed {
+			self.tree[index] = Node::Split;
+			let left_child = index * 2 + 1;
+			let right_child = index * 2 + 2;
+			current_level -= 1;
+			self.push_free_node(current_level, right_child);
+			index = left_child;
+		}
 
-			if current_level == levels_needed {
-				if self.tree[index] == Node::Free {
-					self.tree[index] = Node::Full;
+		self.tree[index] = Node::Full;
 
-					if index > 0 {
-						let parent = self.get_parent_node_index(index);
-						self.update_parent_nodes(parent);
-					}
+		if index > 0 {
+			let parent = self.get_parent_node_index(index);
+			self.update_parent_nodes(parent);
+		}
 
-					break 'down;
-				}
-			} else {
-				match self.tree[index] {
-					Node::Full => {
-						if buddy_exists {
-							// Check if buddy is free
-							index += 1;
-						} else {
-							break 'down;
-						}
-						continue 'down;
-					},
-
-					Node::Free => {
-						// If node is free we split it and descend further down
-						self.tree[index] = Node::Split;
-						index = index * 2 + 1;
-						current_level -= 1;
-						continue 'down;
-					},
-
-					Node::Split => {
-						// Descend further
-						index = index * 2 + 1;
-						current_level -= 1;
-						continue 'down;
-					},
-				}
+		let current_level_offset = (1 << self.levels - current_level) - 1;
+		let level_offset = index - current_level_offset;
+
+		let block_offset = level_offset * (1 << current_level);
+		Ok(block_offset as usize)
+	}
+
+	/// Reallocates the memory pointed to by `ptr`, which must have previously been returned by
+	/// `allocate` or `reallocate`, so that it can hold `new_size` Bytes.
+	///
+	/// Returns `Ok(ptr)` unchanged if the block could be resized without moving it: the new size
+	/// still fits the same buddy block, the block could be shrunk in place, or it could be
+	/// grown in place because its buddy was free at every level up to the new order.
+	///
+	/// Otherwise the data has to move. A successful move returns a different, freshly
+	/// allocated pointer; the old pointer is deallocated by this call and **the caller must
+	/// copy the old contents into the new pointer** before using it, and must not use the old
+	/// pointer again.
+	///
+	/// Returns `Err(Error::OutOfMemory)` or `Err(Error::RequestTooLarge)` if a move was required
+	/// but no replacement block was available. Unlike a successful move, **the old pointer is
+	/// left untouched and still owned by the caller** in that case — nothing has been freed, and
+	/// there is nothing to copy.
+	///
+	/// Returns `Err(Error::PointerInvalid)` (without allocating or freeing anything) if `ptr` was
+	/// not currently allocated.
+	pub fn reallocate(&mut self, ptr: u32, new_size: u32) -> Result<u32, Error> {
+		let offset = match ptr.checked_sub(1) {
+			Some(offset) => offset,
+			None => return Err(Error::PointerInvalid),
+		};
+
+		let old_size = match self.allocated_bytes.get(&offset) {
+			Some(v) => *v,
+			None => return Err(Error::PointerInvalid),
+		};
+
+		let old_order = Heap::get_tree_levels((old_size + BLOCK_SIZE - 1) / BLOCK_SIZE);
+		let new_order = Heap::get_tree_levels((new_size + BLOCK_SIZE - 1) / BLOCK_SIZE);
+
+		if new_order == old_order {
+			self.allocated_bytes.insert(offset, new_size);
+			self.total_size = self.total_size - old_size + new_size;
+			self.bump_max_size();
+			return Ok(ptr);
+		}
+
+		if new_order < old_order {
+			// Shrinking: split the occupied node back down towards the leaves, keeping the
+			// lower-indexed (same-offset) child occupied and freeing the now-surplus buddy
+			// half at each level.
+			let current_level_offset = (1 << self.levels - old_order) - 1;
+			let level_offset = (offset / BLOCK_SIZE) / (1 << old_order);
+			let index = (current_level_offset + level_offset) as usize;
+
+			let mut shrink_index = index;
+			let mut shrink_level = old_order;
+			while shrink_level > new_order {
+				self.tree[shrink_index] = Node::Split;
+				let left_child = shrink_index * 2 + 1;
+				let right_child = shrink_index * 2 + 2;
+				self.tree[left_child] = Node::Full;
+				shrink_level -= 1;
+				self.free_and_merge(right_child, shrink_level);
+				shrink_index = left_child;
 			}
 
-			if buddy_exists {
-				// If a buddy exists it needs to be checked as well
-				index += 1;
-				continue 'down;
+			if index > 0 {
+				let parent = self.get_parent_node_index(index);
+				self.update_parent_nodes(parent);
 			}
 
-			// Backtrack once we're at the bottom and haven't matched a free block yet
-			'up: loop {
-				if index == 0 {
-					trace!(target: "wasm-heap", "Heap is too small: tree root reached.");
-					return None;
-				}
+			self.allocated_bytes.insert(offset, new_size);
+			self.total_size = self.total_size - old_size + new_size;
+			self.bump_max_size();
+			return Ok(ptr);
+		}
 
-				index = self.get_parent_node_index(index);
-				current_level += 1;
-				let has_buddy = index & 1 == 1;
-				if has_buddy {
-					index += 1;
-					break 'up;
-				}
+		// Growing past the current block's order: try to extend in place by repeatedly
+		// merging with the buddy one level up. This only works while the occupied block is the
+		// lower-addressed (left) child at every level up to `new_order` and each such buddy is
+		// `Free` — merging the other way around would move the block's start address, which
+		// defeats the point of resizing in place.
+		let current_level_offset = (1 << self.levels - old_order) - 1;
+		let level_offset = (offset / BLOCK_SIZE) / (1 << old_order);
+		let index = (current_level_offset + level_offset) as usize;
+
+		if self.can_grow_in_place(index, old_order, new_order) {
+			self.grow_in_place(index, old_order, new_order);
+
+			self.allocated_bytes.insert(offset, new_size);
+			self.total_size = self.total_size - old_size + new_size;
+			self.bump_max_size();
+			return Ok(ptr);
+		}
+
+		// In-place growth isn't possible: the buddy needed at some level belongs to another
+		// allocation (or is itself split further), so fall back to allocating a fresh block
+		// and signal the move by returning a different pointer.
+		let new_ptr = self.allocate(new_size)?;
+		self.deallocate(ptr);
+		Ok(new_ptr)
+	}
+
+	// Read-only check for whether `grow_in_place` could merge the node at `index`/`level` all
+	// the way up to `target_level` without moving its start address. Never mutates the tree, so
+	// a caller can check first and only commit to `grow_in_place` once this returns `true`.
+	fn can_grow_in_place(&self, index: usize, level: u32, target_level: u32) -> bool {
+		let mut index = index;
+		let mut level = level;
+		while level < target_level {
+			// Odd indices are left (lower-addressed) children; merging with the sibling at
+			// `index + 1` keeps the block's start address unchanged.
+			let is_left_child = (index & 1) == 1;
+			if !is_left_child || self.tree[index + 1] != Node::Free {
+				return false;
 			}
+			index = (index + 1) / 2 - 1;
+			level += 1;
 		}
+		true
+	}
 
-		let current_level_offset = (1 << self.levels - current_level) - 1;
-		let level_offset = index - current_level_offset;
+	// Merges the node at `index`/`level` with its buddy, one level at a time, up to
+	// `target_level`. Assumes `can_grow_in_place` already confirmed this is possible.
+	fn grow_in_place(&mut self, index: usize, level: u32, target_level: u32) {
+		let mut index = index;
+		let mut level = level;
+		while level < target_level {
+			self.remove_free_node(level, index + 1);
+			index = self.get_parent_node_index(index);
+			level += 1;
+		}
 
-		let block_offset = level_offset * (1 << current_level);
-		Some(block_offset as usize)
+		self.tree[index] = Node::Full;
+		if index > 0 {
+			let parent = self.get_parent_node_index(index);
+			self.update_parent_nodes(parent);
+		}
 	}
 
-	/// Deallocates all blocks which were allocated for a pointer.
-	pub fn deallocate(&mut self, mut ptr: u32) {
+	/// Reserves the byte range `[offset, offset + size)` so that it will never be handed out by
+	/// `allocate`. This is meant for carving out fixed regions the heap doesn't own, such as the
+	/// wasm module's data segments and stack at the start of linear memory.
+	///
+	/// The range is rounded outwards to `BLOCK_SIZE` boundaries. Returns `false` without
+	/// modifying the tree if any of the covered blocks is already reserved or allocated.
+	pub fn reserve(&mut self, offset: u32, size: u32) -> bool {
+		let start_leaf = offset / BLOCK_SIZE;
+		let end_leaf = (offset + size + BLOCK_SIZE - 1) / BLOCK_SIZE;
+
+		for leaf in start_leaf..end_leaf {
+			if !self.leaf_is_free(leaf) {
+				return false;
+			}
+		}
+
+		for leaf in start_leaf..end_leaf {
+			self.reserve_leaf(leaf);
+		}
+
+		true
+	}
+
+	/// Walks from the tree root down to the given leaf, without mutating anything, to find
+	/// out whether it is currently free. A `Free` or `Full` node encountered along the way
+	/// settles the answer for the whole subtree below it.
+	fn leaf_is_free(&self, leaf: u32) -> bool {
+		let mut index = 0;
+		let mut current_level = self.levels;
+
+		while current_level > 0 {
+			match self.tree[index] {
+				Node::Free => return true,
+				Node::Full => return false,
+				Node::Split => {
+					current_level -= 1;
+					let bit = (leaf >> current_level) & 1;
+					index = index * 2 + 1 + bit as usize;
+				},
+			}
+		}
+
+		self.tree[index] == Node::Free
+	}
+
+	/// Splits `Free` nodes down from the root to the given leaf and marks the leaf `Full`,
+	/// updating ancestors on the way back up. Assumes `leaf_is_free` was already checked.
+	fn reserve_leaf(&mut self, leaf: u32) {
+		let mut index = 0;
+		let mut current_level = self.levels;
+
+		while current_level > 0 {
+			let was_free = self.tree[index] == Node::Free;
+			if was_free {
+				self.remove_free_node(current_level, index);
+				self.tree[index] = Node::Split;
+			}
+
+			current_level -= 1;
+			let bit = (leaf >> current_level) & 1;
+			let left_child = index * 2 + 1;
+			let right_child = index * 2 + 2;
+			let (taken_child, other_child) = if bit == 0 {
+				(left_child, right_child)
+			} else {
+				(right_child, left_child)
+			};
+
+			if was_free {
+				// The split above just created two new free nodes; register the one we are
+				// not descending into.
+				self.push_free_node(current_level, other_child);
+			} else if self.tree[taken_child] == Node::Free {
+				// Descending into a node that is already a registered free block, e.g. the
+				// untouched half of a previous leaf's reservation path in this same call.
+				self.remove_free_node(current_level, taken_child);
+			}
+
+			index = taken_child;
+		}
+
+		self.tree[index] = Node::Full;
+
+		if index > 0 {
+			let parent = self.get_parent_node_index(index);
+			self.update_parent_nodes(parent);
+		}
+	}
+
+	/// Deallocates all blocks which were allocated for a pointer. Returns whether a pointer was
+	/// actually freed; a pointer with nothing allocated for it is a no-op and returns `false`.
+	pub fn deallocate(&mut self, mut ptr: u32) -> bool {
 		ptr -= 1;
 
 		let allocated_size = match self.allocated_bytes.get(&ptr) {
 			Some(v) => *v,
 
 			// If nothing has been allocated for the pointer nothing happens
-			None => return (),
+			None => return false,
 		};
 
 		let count_blocks = (allocated_size + BLOCK_SIZE - 1) / BLOCK_SIZE;
@@ -185,6 +494,7 @@ impl Heap {
 
 		self.total_size = self.total_size.checked_sub(allocated_size).unwrap_or(0);
 		trace!(target: "wasm-heap", "Heap size over {} Bytes after deallocation", self.total_size);
+		true
 	}
 
 	fn free(&mut self, block_offset: u32, count_blocks: u32) {
@@ -197,7 +507,7 @@ impl Heap {
 			trace!(target: "wasm-heap", "Index offset {} is > length of tree {}", index_offset, self.tree.len());
 		}
 
-		self.free_and_merge(index_offset as usize);
+		self.free_and_merge(index_offset as usize, requested_level);
 
 		let parent = self.get_parent_node_index(index_offset as usize);
 		self.update_parent_nodes(parent);
@@ -207,10 +517,11 @@ impl Heap {
 		(index + 1) / 2 - 1
 	}
 
-	fn free_and_merge(&mut self, index: usize) {
+	fn free_and_merge(&mut self, index: usize, level: u32) {
 		self.tree[index] = Node::Free;
 
 		if index == 0 {
+			self.push_free_node(level, index);
 			return;
 		}
 
@@ -222,8 +533,13 @@ impl Heap {
 		};
 
 		if self.tree[other_node] == Node::Free {
+			// Buddies coalesce: drop both from this level's free-list and keep merging one
+			// level up.
+			self.remove_free_node(level, other_node);
 			let parent = self.get_parent_node_index(index);
-			self.free_and_merge(parent);
+			self.free_and_merge(parent, level + 1);
+		} else {
+			self.push_free_node(level, index);
 		}
 	}
 
@@ -264,6 +580,138 @@ impl Heap {
 
 }
 
+// The smallest block a `FreeingBumpHeap` will ever hand out, as a power of two: 2^3 = 8 Bytes.
+// A freed block's free-list link is kept in its header, not in the block itself.
+const FREEING_BUMP_MIN_ORDER: u32 = 3;
+
+// The largest block a `FreeingBumpHeap` will ever hand out, as a power of two: 2^25 = 32 MiB.
+const FREEING_BUMP_MAX_ORDER: u32 = 25;
+
+const FREEING_BUMP_ORDER_COUNT: usize = (FREEING_BUMP_MAX_ORDER - FREEING_BUMP_MIN_ORDER + 1) as usize;
+
+// Every allocation is preceded by an 8-byte header. While the block is live it stores the
+// block's order; while the block is free it stores the offset of the next free block of the
+// same order (or `FREEING_BUMP_EMPTY` if it is the last one).
+const FREEING_BUMP_HEADER_SIZE: u32 = 8;
+
+const FREEING_BUMP_EMPTY: u32 = u32::MAX;
+
+/// An alternative to `Heap` implementing the "freeing bump" allocation scheme used by
+/// smoldot's Substrate executor: a bump allocator for memory that has never been touched,
+/// backed by per-order free lists for memory that has been freed and can be reused.
+///
+/// Unlike `Heap`, both `allocate` and `deallocate` are O(1): there is no tree to descend or
+/// merge, and no `FnvHashMap` tracking allocation sizes, because the order of a live block can
+/// always be recovered from the header stored just ahead of it.
+///
+/// This is a standalone prototype, not a drop-in replacement for `Heap`: it owns its memory as
+/// a private `Vec<u8>` rather than handing out offsets into the wasm module's linear memory, so
+/// nothing in the executor can wire it in as-is, and nothing currently constructs or references
+/// it. Landing it unused and unintegrated is intentional for now.
+pub struct FreeingBumpHeap {
+	// `free_lists[order - FREEING_BUMP_MIN_ORDER]` is the offset of the first free block of
+	// that order, or `FREEING_BUMP_EMPTY` if there is none.
+	free_lists: vec::Vec<u32>,
+	// The linear memory backing the heap. Bytes below `bumper` are either live allocations or
+	// free blocks sitting in a free list; bytes at or above `bumper` have never been touched.
+	memory: vec::Vec<u8>,
+	bumper: u32,
+	// Sum of the rounded block sizes (2^order) of all currently live allocations, kept for
+	// parity with `Heap::total_size`. Since the header only recovers a block's order and not
+	// its exact requested size, this tracks occupied Bytes rather than requested Bytes.
+	total_size: u32,
+}
+
+impl FreeingBumpHeap {
+
+	/// Creates a new freeing-bump heap over a fixed-size contiguous region (in Bytes).
+	pub fn new(heap_size: u32) -> Self {
+		FreeingBumpHeap {
+			free_lists: vec![FREEING_BUMP_EMPTY; FREEING_BUMP_ORDER_COUNT],
+			memory: vec![0; heap_size as usize],
+			bumper: 0,
+			total_size: 0,
+		}
+	}
+
+	/// Gets requested number of bytes to allocate and returns the offset of the usable memory
+	/// plus one (just past the block's header), mirroring `Heap::allocate`. Returns `0` if the heap
+	/// cannot serve the request, either because it is larger than the biggest supported order
+	/// or because both the matching free list is empty and the bumper has run out of room.
+	pub fn allocate(&mut self, size: u32) -> u32 {
+		let order = match Self::order_of(size) {
+			Some(order) => order,
+			None => return 0,
+		};
+		let list_index = (order - FREEING_BUMP_MIN_ORDER) as usize;
+
+		let header_offset = if self.free_lists[list_index] != FREEING_BUMP_EMPTY {
+			let header_offset = self.free_lists[list_index];
+			self.free_lists[list_index] = self.read_header(header_offset);
+			header_offset
+		} else {
+			let header_offset = self.bumper;
+			let required = FREEING_BUMP_HEADER_SIZE + (1u32 << order);
+			match self.bumper.checked_add(required) {
+				Some(new_bumper) if new_bumper <= self.memory.len() as u32 => {
+					self.bumper = new_bumper;
+				},
+				_ => return 0,
+			}
+			header_offset
+		};
+
+		self.write_header(header_offset, order);
+		self.total_size += 1u32 << order;
+
+		header_offset + FREEING_BUMP_HEADER_SIZE + 1
+	}
+
+	/// Deallocates the block which was allocated for a pointer, pushing it onto the free list
+	/// for its order so a later `allocate` of a matching size can reuse it.
+	pub fn deallocate(&mut self, mut ptr: u32) {
+		if ptr == 0 {
+			return;
+		}
+		ptr -= 1;
+
+		let header_offset = ptr - FREEING_BUMP_HEADER_SIZE;
+		let order = self.read_header(header_offset);
+		let list_index = (order - FREEING_BUMP_MIN_ORDER) as usize;
+
+		self.write_header(header_offset, self.free_lists[list_index]);
+		self.free_lists[list_index] = header_offset;
+
+		self.total_size = self.total_size.checked_sub(1u32 << order).unwrap_or(0);
+	}
+
+	// Smallest order in `[FREEING_BUMP_MIN_ORDER, FREEING_BUMP_MAX_ORDER]` whose block
+	// (2^order Bytes) can hold `size` Bytes, or `None` if `size` is too large for any order.
+	fn order_of(size: u32) -> Option<u32> {
+		let mut order = FREEING_BUMP_MIN_ORDER;
+		while order <= FREEING_BUMP_MAX_ORDER {
+			if (1u32 << order) >= size {
+				return Some(order);
+			}
+			order += 1;
+		}
+		None
+	}
+
+	fn write_header(&mut self, offset: u32, value: u32) {
+		let offset = offset as usize;
+		self.memory[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+	}
+
+	fn read_header(&self, offset: u32) -> u32 {
+		let offset = offset as usize;
+		let mut bytes = [0u8; 4];
+		bytes.copy_from_slice(&self.memory[offset..offset + 4]);
+		u32::from_le_bytes(bytes)
+	}
+
+}
+
 #[cfg(test)]
 mod tests {
 	use heap::BLOCK_SIZE;
@@ -271,7 +719,7 @@ mod tests {
 	#[test]
 	fn first_pointer_should_be_one() {
 		let mut heap = super::Heap::new(20);
-		let ptr = heap.allocate(5);
+		let ptr = heap.allocate(5).unwrap();
 		assert_eq!(ptr, 1);
 	}
 
@@ -279,7 +727,26 @@ mod tests {
 	fn deallocation_for_nonexistent_pointer_should_not_panic() {
 		let mut heap = super::Heap::new(20);
 		let ret = heap.deallocate(5);
-		assert_eq!(ret, ());
+		assert_eq!(ret, false);
+	}
+
+	#[test]
+	fn allocate_should_fail_with_request_too_large_when_bigger_than_whole_heap() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let result = heap.allocate(heap_size * 2);
+		assert_eq!(result, Err(super::Error::RequestTooLarge));
+	}
+
+	#[test]
+	fn allocate_should_fail_with_out_of_memory_when_heap_is_full() {
+		let heap_size = BLOCK_SIZE * 2;
+		let mut heap = super::Heap::new(heap_size);
+
+		heap.allocate(BLOCK_SIZE * 2).unwrap();
+		let result = heap.allocate(1);
+		assert_eq!(result, Err(super::Error::OutOfMemory));
 	}
 
 	#[test]
@@ -304,7 +771,7 @@ mod tests {
 		let mut heap = super::Heap::new(heap_size);
 		assert_eq!(heap.total_size, 0);
 
-		let ptr = heap.allocate(42);
+		let ptr = heap.allocate(42).unwrap();
 		assert_eq!(heap.total_size, 42);
 
 		heap.deallocate(ptr);
@@ -318,7 +785,7 @@ mod tests {
 		for _ in 1..10 {
 			assert_eq!(heap.total_size, 0);
 
-			let ptr = heap.allocate(42);
+			let ptr = heap.allocate(42).unwrap();
 			assert_eq!(ptr, 1);
 			assert_eq!(heap.total_size, 42);
 
@@ -329,4 +796,187 @@ mod tests {
 		assert_eq!(heap.total_size, 0);
 	}
 
+	#[test]
+	fn reallocate_within_same_order_should_not_move() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let ptr = heap.allocate(42).unwrap();
+		let new_ptr = heap.reallocate(ptr, 100).unwrap();
+
+		assert_eq!(new_ptr, ptr);
+		assert_eq!(heap.total_size, 100);
+	}
+
+	#[test]
+	fn reallocate_shrinking_should_not_move_and_should_free_the_surplus() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let ptr = heap.allocate(BLOCK_SIZE * 2).unwrap();
+		let new_ptr = heap.reallocate(ptr, 5).unwrap();
+
+		assert_eq!(new_ptr, ptr);
+		assert_eq!(heap.total_size, 5);
+
+		// The freed buddy half should now be available for another allocation.
+		let other_ptr = heap.allocate(BLOCK_SIZE);
+		assert!(other_ptr.is_ok());
+	}
+
+	#[test]
+	fn reallocate_growing_past_current_block_should_move() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let first = heap.allocate(BLOCK_SIZE).unwrap();
+		let ptr = heap.allocate(5).unwrap();
+		let new_ptr = heap.reallocate(ptr, BLOCK_SIZE * 2).unwrap();
+
+		assert_ne!(new_ptr, ptr);
+		assert_eq!(heap.total_size, BLOCK_SIZE + BLOCK_SIZE * 2);
+
+		heap.deallocate(first);
+		heap.deallocate(new_ptr);
+	}
+
+	#[test]
+	fn reallocate_growing_with_free_buddy_should_grow_in_place() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let ptr = heap.allocate(5).unwrap();
+		let new_ptr = heap.reallocate(ptr, BLOCK_SIZE * 2).unwrap();
+
+		// Nothing else is allocated, so `ptr`'s buddy is free at every level up to the new
+		// order — the grow should happen in place and keep the same pointer.
+		assert_eq!(new_ptr, ptr);
+		assert_eq!(heap.total_size, BLOCK_SIZE * 2);
+
+		// The block should really have grown: the remaining two blocks of the heap can still be
+		// handed out, but nothing more fits afterwards.
+		assert!(heap.allocate(BLOCK_SIZE).is_ok());
+		assert!(heap.allocate(BLOCK_SIZE).is_ok());
+		assert!(heap.allocate(BLOCK_SIZE).is_err());
+	}
+
+	#[test]
+	fn reallocate_with_unallocated_pointer_should_fail() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let ptr = heap.allocate(5).unwrap();
+		heap.deallocate(ptr);
+
+		assert_eq!(heap.reallocate(ptr, 10), Err(super::Error::PointerInvalid));
+	}
+
+	#[test]
+	fn reserve_should_carve_out_a_fixed_region() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		assert!(heap.reserve(0, BLOCK_SIZE));
+
+		// The reserved block must not be handed out, and must not show up as a tracked
+		// allocation that `deallocate` could release.
+		let ptr = heap.allocate(BLOCK_SIZE * 3).unwrap();
+		assert_ne!(ptr - 1, 0);
+		assert_eq!(heap.total_size, BLOCK_SIZE * 3);
+		assert!(!heap.deallocate(1));
+	}
+
+	#[test]
+	fn reserve_should_fail_on_overlap_and_leave_tree_unchanged() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		assert!(heap.reserve(0, BLOCK_SIZE * 2));
+		assert!(!heap.reserve(BLOCK_SIZE, BLOCK_SIZE));
+
+		// The first reservation should still stand, and the rest of the heap still usable.
+		let ptr = heap.allocate(BLOCK_SIZE * 2).unwrap();
+		assert_ne!(ptr - 1, 0);
+	}
+
+	#[test]
+	fn stats_max_size_should_stay_at_peak_after_deallocation() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		let ptr = heap.allocate(BLOCK_SIZE * 3).unwrap();
+		assert_eq!(heap.stats().max_size, BLOCK_SIZE * 3);
+
+		heap.deallocate(ptr);
+		assert_eq!(heap.total_size, 0);
+		assert_eq!(heap.stats().max_size, BLOCK_SIZE * 3);
+	}
+
+	#[test]
+	fn stats_should_report_live_allocations_and_largest_free_block() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		assert_eq!(heap.stats().largest_free_block, heap_size);
+
+		heap.allocate(BLOCK_SIZE).unwrap();
+		let stats = heap.stats();
+		assert_eq!(stats.allocations, 1);
+		assert_eq!(stats.largest_free_block, BLOCK_SIZE * 2);
+	}
+
+	#[test]
+	fn stats_fragmentation_should_be_zero_for_a_fully_free_heap() {
+		let heap_size = BLOCK_SIZE * 4;
+		let heap = super::Heap::new(heap_size);
+
+		assert_eq!(heap.stats().fragmentation, 0.0);
+	}
+
+	#[test]
+	fn stats_fragmentation_should_be_positive_once_split() {
+		let heap_size = BLOCK_SIZE * 4;
+		let mut heap = super::Heap::new(heap_size);
+
+		heap.allocate(BLOCK_SIZE).unwrap();
+
+		assert!(heap.stats().fragmentation > 0.0);
+	}
+
+	#[test]
+	fn freeing_bump_heap_should_reuse_freed_block_of_the_same_order() {
+		let mut heap = super::FreeingBumpHeap::new(1024);
+
+		let ptr = heap.allocate(42);
+		assert_ne!(ptr, 0);
+		heap.deallocate(ptr);
+
+		let reused_ptr = heap.allocate(42);
+		assert_eq!(reused_ptr, ptr);
+		assert_eq!(heap.total_size, 64);
+	}
+
+	#[test]
+	fn freeing_bump_heap_should_bump_when_free_list_is_empty() {
+		let mut heap = super::FreeingBumpHeap::new(1024);
+
+		let first = heap.allocate(8);
+		let second = heap.allocate(8);
+
+		assert_ne!(first, second);
+		assert_eq!(heap.total_size, 16);
+	}
+
+	#[test]
+	fn freeing_bump_heap_should_fail_once_exhausted() {
+		let mut heap = super::FreeingBumpHeap::new(16);
+
+		// Header (8 Bytes) + smallest order (8 Bytes) fits exactly once.
+		let first = heap.allocate(8);
+		assert_ne!(first, 0);
+
+		let second = heap.allocate(8);
+		assert_eq!(second, 0);
+	}
+
 }